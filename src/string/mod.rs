@@ -0,0 +1,173 @@
+//! Fixed-capacity, stack-allocated UTF-8 string
+//!
+//! [`StaticString<N>`] stores up to `N` bytes inline and never allocates. [`SpillString<N>`]
+//! (behind the `alloc` feature) is the companion type that spills to the heap past `N` bytes.
+
+mod utils;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+mod spill_string;
+#[cfg(feature = "alloc")]
+pub use spill_string::SpillString;
+
+use utils::is_inside_boundary;
+
+/// Errors returned by `StaticString`'s fallible operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+  /// The operation would have produced invalid utf-8
+  Utf8,
+  /// The operation would have exceeded the string's fixed capacity
+  OutOfBounds,
+}
+
+/// A fixed-capacity, stack-allocated string of up to `N` bytes
+pub struct StaticString<const N: usize> {
+  /// Backing storage; only the first `size` bytes are initialized, valid utf-8
+  buffer: [u8; N],
+  /// Number of initialized, valid-utf8 bytes currently in `buffer`
+  pub(crate) size: usize,
+}
+
+impl<const N: usize> Default for StaticString<N> {
+  #[inline]
+  fn default() -> Self {
+    Self { buffer: [0; N], size: 0 }
+  }
+}
+
+impl<const N: usize> StaticString<N> {
+  /// Creates a new, empty `StaticString`
+  #[inline]
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Builds a `StaticString` from a `&str`, failing if it doesn't fit inside `N` bytes
+  ///
+  /// # Errors
+  ///
+  /// Returns [`Error::OutOfBounds`] if `s` is longer than `N` bytes
+  #[inline]
+  pub fn try_from_str(s: impl AsRef<str>) -> Result<Self, Error> {
+    let s = s.as_ref();
+    is_inside_boundary(s.len(), N)?;
+    let mut out = Self::default();
+    out.buffer[..s.len()].copy_from_slice(s.as_bytes());
+    out.size = s.len();
+    Ok(out)
+  }
+
+  /// Fixed capacity of this `StaticString`, always `N`
+  #[inline]
+  #[must_use]
+  pub fn capacity(&self) -> usize {
+    N
+  }
+
+  /// Current length in bytes
+  #[inline]
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.size
+  }
+
+  /// Returns `true` if the string is empty
+  #[inline]
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.size == 0
+  }
+
+  /// Borrows the initialized bytes as a `&str`
+  #[inline]
+  #[must_use]
+  pub fn as_str(&self) -> &str {
+    // Safety: `buffer[..size]` only ever holds bytes copied from a `&str` or written through the
+    // shared utf-8 encoder
+    unsafe { core::str::from_utf8_unchecked(self.buffer.get_unchecked(..self.size)) }
+  }
+
+  /// Mutably borrows the whole backing buffer, including the uninitialized tail past `size`
+  #[inline]
+  pub(crate) fn as_mut_bytes(&mut self) -> &mut [u8] {
+    &mut self.buffer
+  }
+
+  /// Builds a new `StaticString` from arbitrary bytes, replacing invalid UTF-8 sequences with
+  /// `U+FFFD`; truncates at the last char boundary that fits within `N`
+  #[inline]
+  #[must_use]
+  pub fn from_utf8_lossy(bytes: &[u8]) -> Self {
+    utils::from_utf8_lossy(bytes)
+  }
+
+  /// Builds a new `StaticString` from raw bytes, replacing invalid UTF-8 sequences with `U+FFFD`
+  ///
+  /// Alias of [`Self::from_utf8_lossy`] for callers that think in terms of bytes rather than
+  /// strings
+  #[inline]
+  #[must_use]
+  pub fn from_bytes_lossy(bytes: &[u8]) -> Self {
+    utils::from_bytes_lossy(bytes)
+  }
+
+  /// Decodes a UTF-16 code-unit slice into a new `StaticString`
+  ///
+  /// # Errors
+  ///
+  /// Returns [`Error::Utf8`] on an unpaired or mismatched surrogate, or [`Error::OutOfBounds`]
+  /// if the decoded string wouldn't fit inside `N` bytes
+  #[inline]
+  pub fn from_utf16(src: &[u16]) -> Result<Self, Error> {
+    utils::from_utf16(src)
+  }
+
+  /// Decodes a UTF-16 code-unit slice into a new `StaticString`, replacing unpaired or
+  /// mismatched surrogates with `U+FFFD`; truncates at the last char boundary that fits within
+  /// `N`
+  #[inline]
+  #[must_use]
+  pub fn from_utf16_lossy(src: &[u16]) -> Self {
+    utils::from_utf16_lossy(src)
+  }
+
+  /// Returns the byte offset of the first occurrence of `needle` inside this string
+  #[inline]
+  #[must_use]
+  pub fn find(&self, needle: &str) -> Option<usize> {
+    utils::find(self.as_str(), needle)
+  }
+
+  /// Returns the byte offset of the last occurrence of `needle` inside this string
+  #[inline]
+  #[must_use]
+  pub fn rfind(&self, needle: &str) -> Option<usize> {
+    utils::rfind(self.as_str(), needle)
+  }
+
+  /// Returns `true` if `needle` occurs anywhere inside this string
+  #[inline]
+  #[must_use]
+  pub fn contains(&self, needle: &str) -> bool {
+    utils::contains(self.as_str(), needle)
+  }
+
+  /// Replaces the bytes in `range` with `replacement`, shifting the tail to open or close the gap
+  ///
+  /// # Errors
+  ///
+  /// Returns [`Error::Utf8`] if either endpoint isn't a char boundary, or [`Error::OutOfBounds`]
+  /// if the range is inverted, out of bounds, or the resulting string wouldn't fit in `N`
+  #[inline]
+  pub fn replace_range(&mut self, range: core::ops::Range<usize>, replacement: &str) -> Result<(), Error> {
+    utils::replace_range(self, range, replacement)
+  }
+}
+
+#[cfg(test)]
+/// Small `StaticString` alias shared by this module's unit tests
+type SmallString = StaticString<20>;
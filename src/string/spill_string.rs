@@ -0,0 +1,358 @@
+//! A capacity-`N` inline string that transparently spills to the heap, feature-gated behind `alloc`
+//!
+//! [`SpillString`] stores its bytes inline exactly like [`StaticString`](super::StaticString) for
+//! as long as they fit in `N` bytes, then migrates to a heap allocation the moment a mutation
+//! would overflow it. Callers get the no-alloc fast path for small strings without a hard
+//! capacity ceiling, the same trade-off small-string-optimized runtimes make for general-purpose
+//! strings.
+
+use super::utils::{encode_char_utf8_unchecked_into, is_char_boundary_str, shift_unchecked, truncate_str};
+use super::Error;
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, realloc, Layout};
+use core::{ptr, slice, str};
+
+/// Heap-backed arm of [`SpillString`], used once the inline buffer overflows
+struct Boxed {
+  /// Pointer to the heap allocation holding the string's bytes
+  ptr: ptr::NonNull<u8>,
+  /// Number of initialized, valid-utf8 bytes
+  len: usize,
+  /// Size in bytes of the allocation `ptr` points to
+  capacity: usize,
+}
+
+impl Boxed {
+  /// Allocates a new heap buffer of at least `capacity` bytes, empty
+  fn with_capacity(capacity: usize) -> Self {
+    debug_assert!(capacity > 0);
+    let layout = Layout::array::<u8>(capacity).expect("capacity overflows isize");
+    // Safety: `layout` has non-zero size, checked above
+    let ptr = unsafe { alloc(layout) };
+    let ptr = ptr::NonNull::new(ptr).unwrap_or_else(|| handle_alloc_error(layout));
+    Self { ptr, len: 0, capacity }
+  }
+
+  /// Grows the allocation to at least `capacity` bytes, preserving existing contents
+  fn grow_to(&mut self, capacity: usize) {
+    debug_assert!(capacity > self.capacity);
+    let old_layout = Layout::array::<u8>(self.capacity).expect("capacity overflows isize");
+    let new_layout = Layout::array::<u8>(capacity).expect("capacity overflows isize");
+    // Safety: `self.ptr` was allocated with `old_layout`, `new_layout` has non-zero size
+    let ptr = unsafe { realloc(self.ptr.as_ptr(), old_layout, new_layout.size()) };
+    self.ptr = ptr::NonNull::new(ptr).unwrap_or_else(|| handle_alloc_error(new_layout));
+    self.capacity = capacity;
+  }
+
+  /// Raw bytes currently considered initialized
+  fn as_slice(&self) -> &[u8] {
+    // Safety: `self.len` bytes starting at `self.ptr` are always initialized
+    unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+  }
+
+  /// The whole backing allocation, usable as a write target up to `self.capacity`
+  fn as_mut_buffer(&mut self) -> &mut [u8] {
+    // Safety: `self.ptr` points to an allocation of `self.capacity` bytes
+    unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.capacity) }
+  }
+}
+
+impl Drop for Boxed {
+  fn drop(&mut self) {
+    let layout = Layout::array::<u8>(self.capacity).expect("capacity overflows isize");
+    // Safety: `self.ptr` was allocated with `layout` and is only freed here
+    unsafe { dealloc(self.ptr.as_ptr(), layout) };
+  }
+}
+
+/// Either arm of a [`SpillString`]: inline (no allocation) or heap-backed (spilled)
+enum Repr<const N: usize> {
+  /// Bytes live directly inside the `SpillString`, just like `StaticString<N>`
+  Inline {
+    /// Uninitialized-at-the-tail inline buffer
+    buffer: [u8; N],
+    /// Number of initialized, valid-utf8 bytes
+    size: usize,
+  },
+  /// Bytes live in a heap allocation, grown geometrically as needed
+  Boxed(Boxed),
+}
+
+/// A string that stores up to `N` bytes inline and spills to the heap past that, requires `alloc`
+///
+/// Mirrors [`StaticString<N>`](super::StaticString)'s inline representation and mutators, but a
+/// push/insert that would overflow `N` migrates the contents to a heap allocation instead of
+/// failing, so `SpillString` has no capacity ceiling
+pub struct SpillString<const N: usize> {
+  /// Which arm (inline or boxed) currently backs this string
+  repr: Repr<N>,
+}
+
+impl<const N: usize> SpillString<N> {
+  /// Creates a new, empty, inline `SpillString`
+  #[inline]
+  #[must_use]
+  pub fn new() -> Self {
+    Self { repr: Repr::Inline { buffer: [0; N], size: 0 } }
+  }
+
+  /// Current length in bytes
+  #[inline]
+  #[must_use]
+  pub fn len(&self) -> usize {
+    match &self.repr {
+      Repr::Inline { size, .. } => *size,
+      Repr::Boxed(boxed) => boxed.len,
+    }
+  }
+
+  /// Returns `true` if the string is empty
+  #[inline]
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns `true` if the contents currently live on the heap
+  #[inline]
+  #[must_use]
+  pub fn is_spilled(&self) -> bool {
+    matches!(self.repr, Repr::Boxed(_))
+  }
+
+  /// Bytes of the active arm, up to `self.len()`
+  #[inline]
+  fn as_bytes(&self) -> &[u8] {
+    match &self.repr {
+      Repr::Inline { buffer, size } => &buffer[..*size],
+      Repr::Boxed(boxed) => boxed.as_slice(),
+    }
+  }
+
+  /// String slice over the active arm's initialized bytes
+  #[inline]
+  #[must_use]
+  pub fn as_str(&self) -> &str {
+    // Safety: both arms only ever contain bytes copied from a `&str` or written through the
+    // shared utf-8 encoder
+    unsafe { str::from_utf8_unchecked(self.as_bytes()) }
+  }
+
+  /// Writable view over the active arm's whole backing buffer (inline array or heap allocation)
+  #[inline]
+  fn as_mut_buffer(&mut self) -> &mut [u8] {
+    match &mut self.repr {
+      Repr::Inline { buffer, .. } => buffer.as_mut_slice(),
+      Repr::Boxed(boxed) => boxed.as_mut_buffer(),
+    }
+  }
+
+  /// Total size, in bytes, of the active arm's backing buffer
+  #[inline]
+  fn buffer_capacity(&self) -> usize {
+    match &self.repr {
+      Repr::Inline { .. } => N,
+      Repr::Boxed(boxed) => boxed.capacity,
+    }
+  }
+
+  /// Ensures at least `additional` more bytes can be written, spilling to (or growing) the heap
+  /// allocation as needed; never touches bytes past the current length
+  fn reserve(&mut self, additional: usize) {
+    let required = self.len().saturating_add(additional);
+    if required <= self.buffer_capacity() {
+      return;
+    }
+
+    let grown = self.buffer_capacity().saturating_mul(2).max(required);
+    match &mut self.repr {
+      Repr::Inline { buffer, size } => {
+        let mut boxed = Boxed::with_capacity(grown);
+        // Safety: `*size` bytes are initialized in `buffer`, `boxed`'s allocation is `grown >= *size` bytes
+        unsafe { ptr::copy_nonoverlapping(buffer.as_ptr(), boxed.as_mut_buffer().as_mut_ptr(), *size) };
+        boxed.len = *size;
+        self.repr = Repr::Boxed(boxed);
+      },
+      Repr::Boxed(boxed) => boxed.grow_to(grown),
+    }
+  }
+
+  /// Sets the tracked length after bytes have been written directly into the active buffer
+  #[inline]
+  fn set_len(&mut self, len: usize) {
+    match &mut self.repr {
+      Repr::Inline { size, .. } => *size = len,
+      Repr::Boxed(boxed) => boxed.len = len,
+    }
+  }
+
+  /// Appends a string slice, spilling to the heap if it doesn't fit inline
+  #[inline]
+  pub fn push_str(&mut self, string: &str) {
+    self.reserve(string.len());
+    let index = self.len();
+    let len = string.len();
+    self.as_mut_buffer()[index..index + len].copy_from_slice(string.as_bytes());
+    self.set_len(index + len);
+  }
+
+  /// Appends a single `char`, spilling to the heap if it doesn't fit inline
+  #[inline]
+  pub fn push(&mut self, ch: char) {
+    self.reserve(ch.len_utf8());
+    let index = self.len();
+    // Safety: `reserve` just guaranteed room for `ch.len_utf8()` more bytes past `index`
+    unsafe { encode_char_utf8_unchecked_into(self.as_mut_buffer(), ch, index) };
+    self.set_len(index + ch.len_utf8());
+  }
+
+  /// Inserts a `char` at byte offset `index`, spilling to the heap if it doesn't fit inline
+  ///
+  /// # Errors
+  ///
+  /// Returns [`Error::Utf8`] if `index` is not a char boundary
+  #[inline]
+  pub fn insert(&mut self, index: usize, ch: char) -> Result<(), Error> {
+    is_char_boundary_str(self.as_str(), index)?;
+
+    self.reserve(ch.len_utf8());
+    let len = self.len();
+    // Safety: `index <= len`, checked via `is_char_boundary_str` above
+    unsafe { shift_unchecked(self.as_mut_buffer(), index, index + ch.len_utf8(), len - index) };
+    // Safety: `reserve` guaranteed room for `ch.len_utf8()` more bytes, and the shift above opened the gap
+    unsafe { encode_char_utf8_unchecked_into(self.as_mut_buffer(), ch, index) };
+    self.set_len(len + ch.len_utf8());
+    Ok(())
+  }
+
+  /// Shortens the string to `new_len` bytes, truncating at the last char boundary if `new_len`
+  /// falls inside a char; never reallocates or spills back to inline
+  #[inline]
+  pub fn truncate(&mut self, new_len: usize) {
+    if new_len >= self.len() {
+      return;
+    }
+    let new_len = truncate_str(self.as_str(), new_len).len();
+    self.set_len(new_len);
+  }
+}
+
+impl<const N: usize> Default for SpillString<N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn push_str_stays_inline() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<8>::new();
+    s.push_str("hi");
+    assert!(!s.is_spilled());
+    assert_eq!(s.as_str(), "hi");
+  }
+
+  #[test]
+  fn push_str_spills() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<8>::new();
+    s.push_str("this is longer than eight bytes");
+    assert!(s.is_spilled());
+    assert_eq!(s.as_str(), "this is longer than eight bytes");
+  }
+
+  #[test]
+  fn push_char_by_char_spills() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<4>::new();
+    for ch in "abcde🤔".chars() {
+      s.push(ch);
+    }
+    assert!(s.is_spilled());
+    assert_eq!(s.as_str(), "abcde🤔");
+  }
+
+  #[test]
+  fn insert_inline_and_spilled() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<8>::new();
+    s.push_str("helo");
+    s.insert(3, 'l').unwrap();
+    assert!(!s.is_spilled());
+    assert_eq!(s.as_str(), "hello");
+
+    s.insert(5, ' ').unwrap();
+    s.push_str("world");
+    assert!(s.is_spilled());
+    assert_eq!(s.as_str(), "hello world");
+  }
+
+  #[test]
+  fn insert_rejects_non_char_boundary() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<8>::new();
+    s.push_str("🤔");
+    assert!(s.insert(1, 'a').is_err());
+  }
+
+  #[test]
+  fn truncate_inline() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<8>::new();
+    s.push_str("hello");
+    s.truncate(3);
+    assert_eq!(s.as_str(), "hel");
+  }
+
+  #[test]
+  fn truncate_after_spilling() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<4>::new();
+    s.push_str("hello world");
+    assert!(s.is_spilled());
+    s.truncate(5);
+    assert_eq!(s.as_str(), "hello");
+    assert!(s.is_spilled());
+  }
+
+  #[test]
+  fn truncate_at_char_boundary() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<8>::new();
+    s.push_str("a🤔b");
+    s.truncate(3);
+    assert_eq!(s.as_str(), "a");
+  }
+
+  #[test]
+  fn grows_after_spilling() {
+    let _ = env_logger::try_init();
+    let mut s = SpillString::<4>::new();
+    s.push_str("already past inline capacity");
+    assert!(s.is_spilled());
+    for _ in 0..50 {
+      s.push('x');
+    }
+    assert!(s.is_spilled());
+    assert_eq!(s.len(), "already past inline capacity".len() + 50);
+    assert!(s.as_str().ends_with(&"x".repeat(50)));
+  }
+
+  #[test]
+  fn drop_inline_and_spilled_does_not_leak_or_double_free() {
+    let _ = env_logger::try_init();
+    for _ in 0..100 {
+      let mut s = SpillString::<16>::new();
+      s.push_str("stays inline");
+      drop(s);
+
+      let mut s = SpillString::<4>::new();
+      s.push_str("this one definitely spills to the heap");
+      drop(s);
+    }
+  }
+}
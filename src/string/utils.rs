@@ -3,6 +3,9 @@
 use super::{Error, StaticString};
 use core::ptr::copy;
 
+#[cfg(test)]
+use super::SmallString;
+
 pub(crate) trait IntoLossy<T>: Sized {
   fn into_lossy(self) -> T;
 }
@@ -38,6 +41,22 @@ pub(crate) unsafe fn encode_char_utf8_unchecked<const N: usize>(
   index: usize,
 )
 {
+  debug_assert!(ch.len_utf8().saturating_add(index) <= s.capacity());
+  debug_assert!(ch.len_utf8().saturating_add(s.len()) <= s.capacity());
+  encode_char_utf8_unchecked_into(s.as_mut_bytes(), ch, index);
+}
+
+/// Encodes `char` into a raw byte buffer at specified position, heavily unsafe
+///
+/// Shared by every buffer-owning representation (`StaticString`'s inline array, `SpillString`'s
+/// inline or heap-backed arm, ...) so the UTF-8 encoding logic only lives in one place
+///
+/// # Safety
+///
+/// - It's UB if index is outside of buffer's boundaries (buffer needs at most 4 bytes)
+/// - It's UB if index is inside a character (like a index 3 for "a🤔")
+#[inline]
+pub(crate) unsafe fn encode_char_utf8_unchecked_into(dst: &mut [u8], ch: char, index: usize) {
   // UTF-8 ranges and tags for encoding characters
   #[allow(clippy::missing_docs_in_private_items)]
   const TAG_CONT: u8 = 0b1000_0000;
@@ -54,9 +73,7 @@ pub(crate) unsafe fn encode_char_utf8_unchecked<const N: usize>(
   #[allow(clippy::missing_docs_in_private_items)]
   const MAX_THREE_B: u32 = 0x10000;
 
-  debug_assert!(ch.len_utf8().saturating_add(index) <= s.capacity());
-  debug_assert!(ch.len_utf8().saturating_add(s.len()) <= s.capacity());
-  let dst = s.as_mut_bytes().get_unchecked_mut(index..);
+  let dst = dst.get_unchecked_mut(index..);
   let code = ch as u32;
 
   if code < MAX_ONE_B {
@@ -81,8 +98,12 @@ pub(crate) unsafe fn encode_char_utf8_unchecked<const N: usize>(
 }
 
 /// Copies part of slice to another part (`mem::copy`, basically `memmove`)
+///
+/// Operates on a raw `&mut [u8]` rather than `StaticString` so it's shared by every
+/// buffer-owning representation (`StaticString`'s inline array, `SpillString`'s inline or
+/// heap-backed arm, ...)
 #[inline]
-unsafe fn shift_unchecked(s: &mut [u8], from: usize, to: usize, len: usize) {
+pub(crate) unsafe fn shift_unchecked(s: &mut [u8], from: usize, to: usize, len: usize) {
   debug_assert!(to.saturating_add(len) <= s.len() && from.saturating_add(len) <= s.len());
   let (f, t) = (s.as_ptr().add(from), s.as_mut_ptr().add(to));
   copy(f, t, len);
@@ -123,6 +144,145 @@ pub(crate) unsafe fn shift_left_unchecked<const N: usize>(
   shift_unchecked(s.as_mut_bytes(), from, to, len);
 }
 
+/// Builds a new `StaticString` from arbitrary bytes, replacing invalid UTF-8 sequences with `U+FFFD`
+///
+/// Repeatedly calls [`core::str::from_utf8`] on the remaining slice: on `Ok`, copies as many
+/// valid bytes as fit within `N`; on `Err`, copies the valid prefix, writes one replacement
+/// char via [`encode_char_utf8_unchecked`], skips the erroneous bytes and continues. Once the
+/// next char or replacement would overflow the buffer, stops at the last char boundary (same
+/// rule as [`truncate_str`]) so the result is always valid and never overflows
+#[inline]
+pub(crate) fn from_utf8_lossy<const N: usize>(bytes: &[u8]) -> StaticString<N> {
+  const REPLACEMENT: char = '\u{FFFD}';
+
+  let mut out = StaticString::default();
+  let mut rest = bytes;
+
+  'outer: loop {
+    let (valid, rest_after_error) = match core::str::from_utf8(rest) {
+      Ok(valid) => (valid, None),
+      Err(e) => {
+        let valid_up_to = e.valid_up_to();
+        // Safety: `from_utf8` already confirmed these bytes are valid utf-8
+        let valid = unsafe { core::str::from_utf8_unchecked(rest.get_unchecked(..valid_up_to)) };
+        let skip = e.error_len().unwrap_or_else(|| rest.len() - valid_up_to);
+        (valid, Some(unsafe { rest.get_unchecked(valid_up_to.saturating_add(skip)..) }))
+      },
+    };
+
+    let remaining = out.capacity() - out.len();
+    let copied = truncate_str(valid, remaining);
+    let (index, len) = (out.len(), copied.len());
+    unsafe {
+      out
+        .as_mut_bytes()
+        .get_unchecked_mut(index..index + len)
+        .copy_from_slice(copied.as_bytes());
+    }
+    out.size += len;
+
+    let Some(rest_after_error) = rest_after_error else { break 'outer };
+    if len < valid.len() || out.len() + REPLACEMENT.len_utf8() > out.capacity() {
+      break 'outer;
+    }
+
+    let index = out.len();
+    unsafe { encode_char_utf8_unchecked(&mut out, REPLACEMENT, index) };
+    out.size += REPLACEMENT.len_utf8();
+
+    if rest_after_error.is_empty() {
+      break 'outer;
+    }
+    rest = rest_after_error;
+  }
+
+  out
+}
+
+/// Builds a new `StaticString` from raw bytes, replacing invalid UTF-8 sequences with `U+FFFD`
+///
+/// Alias of [`from_utf8_lossy`] for callers that think in terms of bytes rather than strings
+#[inline]
+pub(crate) fn from_bytes_lossy<const N: usize>(bytes: &[u8]) -> StaticString<N> {
+  from_utf8_lossy(bytes)
+}
+
+/// Combines a UTF-16 surrogate pair into its scalar value
+///
+/// `hi` must be in `0xD800..=0xDBFF` and `lo` in `0xDC00..=0xDFFF`
+#[inline]
+fn decode_surrogate_pair(hi: u16, lo: u16) -> u32 {
+  0x10000_u32 + ((u32::from(hi) - 0xD800) << 10) + (u32::from(lo) - 0xDC00)
+}
+
+/// Decodes a UTF-16 code-unit slice into a new `StaticString`
+///
+/// An unpaired or mismatched surrogate yields [`Error::Utf8`]; a decoded char that would not
+/// fit inside `N` yields [`Error::OutOfBounds`]
+#[inline]
+pub(crate) fn from_utf16<const N: usize>(src: &[u16]) -> Result<StaticString<N>, Error> {
+  let mut out = StaticString::default();
+  let mut units = src.iter().copied();
+
+  while let Some(unit) = units.next() {
+    let ch = match unit {
+      0xD800..=0xDBFF => {
+        let lo = units.next().ok_or(Error::Utf8)?;
+        if !(0xDC00..=0xDFFF).contains(&lo) {
+          return Err(Error::Utf8);
+        }
+        // Safety: surrogate pairs always decode to a valid scalar value
+        unsafe { char::from_u32_unchecked(decode_surrogate_pair(unit, lo)) }
+      },
+      0xDC00..=0xDFFF => return Err(Error::Utf8),
+      unit => char::from_u32(u32::from(unit)).ok_or(Error::Utf8)?,
+    };
+
+    is_inside_boundary(out.len() + ch.len_utf8(), out.capacity())?;
+    let index = out.len();
+    unsafe { encode_char_utf8_unchecked(&mut out, ch, index) };
+    out.size += ch.len_utf8();
+  }
+
+  Ok(out)
+}
+
+/// Decodes a UTF-16 code-unit slice into a new `StaticString`, replacing unpaired or mismatched
+/// surrogates with `U+FFFD`
+///
+/// Stops decoding once the next char would overflow the buffer, same as [`from_utf8_lossy`]
+#[inline]
+pub(crate) fn from_utf16_lossy<const N: usize>(src: &[u16]) -> StaticString<N> {
+  const REPLACEMENT: char = '\u{FFFD}';
+
+  let mut out = StaticString::default();
+  let mut units = src.iter().copied().peekable();
+
+  while let Some(unit) = units.next() {
+    let ch = match unit {
+      0xD800..=0xDBFF => match units.peek().copied() {
+        Some(lo @ 0xDC00..=0xDFFF) => {
+          units.next();
+          // Safety: surrogate pairs always decode to a valid scalar value
+          unsafe { char::from_u32_unchecked(decode_surrogate_pair(unit, lo)) }
+        },
+        _ => REPLACEMENT,
+      },
+      0xDC00..=0xDFFF => REPLACEMENT,
+      unit => char::from_u32(u32::from(unit)).unwrap_or(REPLACEMENT),
+    };
+
+    if out.len() + ch.len_utf8() > out.capacity() {
+      break;
+    }
+    let index = out.len();
+    unsafe { encode_char_utf8_unchecked(&mut out, ch, index) };
+    out.size += ch.len_utf8();
+  }
+
+  out
+}
+
 /// Returns error if size is outside of specified boundary
 #[inline]
 pub fn is_inside_boundary(size: usize, limit: usize) -> Result<(), Error> {
@@ -132,7 +292,16 @@ pub fn is_inside_boundary(size: usize, limit: usize) -> Result<(), Error> {
 /// Returns error if index is not at a valid utf-8 char boundary
 #[inline]
 pub fn is_char_boundary<const N: usize>(s: &StaticString<N>, idx: usize) -> Result<(), Error> {
-  if s.as_str().is_char_boundary(idx) {
+  is_char_boundary_str(s.as_str(), idx)
+}
+
+/// Returns error if index is not at a valid utf-8 char boundary
+///
+/// Operates on a plain `&str` so it's shared by every string representation, not just
+/// `StaticString`'s
+#[inline]
+pub(crate) fn is_char_boundary_str(s: &str, idx: usize) -> Result<(), Error> {
+  if s.is_char_boundary(idx) {
     return Ok(());
   }
   Err(Error::Utf8)
@@ -154,6 +323,184 @@ pub(crate) fn truncate_str(slice: &str, size: usize) -> &str {
   }
 }
 
+/// Every lane holds `0x01`, used to broadcast/test bytes a word at a time (SWAR)
+const LO: usize = usize::from_ne_bytes([0x01; core::mem::size_of::<usize>()]);
+/// Every lane holds `0x80`, the high bit tested by the SWAR zero-byte trick
+const HI: usize = usize::from_ne_bytes([0x80; core::mem::size_of::<usize>()]);
+/// Number of bytes scanned per SWAR word
+const WORD: usize = core::mem::size_of::<usize>();
+
+/// Index of the lowest-addressed lane with a zero byte in `mask`, a SWAR zero-byte mask
+#[inline]
+fn first_zero_lane(mask: usize) -> usize {
+  if cfg!(target_endian = "little") {
+    (mask.trailing_zeros() / 8) as usize
+  } else {
+    (mask.leading_zeros() / 8) as usize
+  }
+}
+
+/// Index of the highest-addressed lane with a zero byte in `mask`, a SWAR zero-byte mask
+#[inline]
+fn last_zero_lane(mask: usize) -> usize {
+  if cfg!(target_endian = "little") {
+    WORD - 1 - (mask.leading_zeros() / 8) as usize
+  } else {
+    WORD - 1 - (mask.trailing_zeros() / 8) as usize
+  }
+}
+
+/// Finds the first occurrence of `needle` in `haystack`, scanning a `usize` at a time (SWAR)
+///
+/// Broadcasts `needle` to every lane of a word, XORs it into the haystack word and tests
+/// `(x.wrapping_sub(LO)) & !x & HI`: a nonzero result means some lane's byte matched. The tail
+/// that doesn't fill a whole word falls back to a plain byte loop
+#[inline]
+pub(crate) fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+  let needle_word = (needle as usize).wrapping_mul(LO);
+  let mut i = 0;
+
+  while i + WORD <= haystack.len() {
+    // Safety: `i + WORD <= haystack.len()`, checked above
+    let chunk = unsafe { haystack.as_ptr().add(i).cast::<usize>().read_unaligned() };
+    let x = chunk ^ needle_word;
+    let zeros = x.wrapping_sub(LO) & !x & HI;
+    if zeros != 0 {
+      return Some(i + first_zero_lane(zeros));
+    }
+    i += WORD;
+  }
+
+  haystack.get(i..)?.iter().position(|&b| b == needle).map(|pos| i + pos)
+}
+
+/// Finds the last occurrence of `needle` in `haystack`, scanning a `usize` at a time (SWAR)
+///
+/// Mirror of [`memchr`], scanning from the end of `haystack` backwards. Uses an exact per-lane
+/// zero test instead of `memchr`'s borrow-based one, which can also flag a lane above a real
+/// match and so cannot be trusted for its highest flagged lane
+#[inline]
+pub(crate) fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+  let needle_word = (needle as usize).wrapping_mul(LO);
+  let mut end = haystack.len();
+
+  while end >= WORD {
+    let i = end - WORD;
+    // Safety: `i + WORD <= haystack.len()`, guaranteed by the loop condition
+    let chunk = unsafe { haystack.as_ptr().add(i).cast::<usize>().read_unaligned() };
+    let x = chunk ^ needle_word;
+    let zeros = !((x & !HI).wrapping_add(!HI) | x | !HI);
+    if zeros != 0 {
+      return Some(i + last_zero_lane(zeros));
+    }
+    end = i;
+  }
+
+  haystack.get(..end)?.iter().rposition(|&b| b == needle)
+}
+
+/// Finds the byte offset of the first occurrence of `needle` inside `haystack`
+///
+/// Seeds candidate positions with [`memchr`] on the needle's first byte, then verifies the
+/// full match, avoiding a naive `O(haystack * needle)` scan in the common case
+#[inline]
+pub(crate) fn find(haystack: &str, needle: &str) -> Option<usize> {
+  if needle.is_empty() {
+    return Some(0);
+  }
+  let (haystack, needle) = (haystack.as_bytes(), needle.as_bytes());
+  let mut start = 0;
+  while needle.len() <= haystack.len() - start {
+    let pos = start + memchr(needle[0], &haystack[start..])?;
+    if haystack.len() - pos < needle.len() {
+      return None;
+    }
+    if &haystack[pos..pos + needle.len()] == needle {
+      return Some(pos);
+    }
+    start = pos + 1;
+  }
+  None
+}
+
+/// Finds the byte offset of the last occurrence of `needle` inside `haystack`
+///
+/// Seeds candidate positions with [`memrchr`] on the needle's last byte, then verifies the
+/// full match
+#[inline]
+pub(crate) fn rfind(haystack: &str, needle: &str) -> Option<usize> {
+  if needle.is_empty() {
+    return Some(haystack.len());
+  }
+  let (haystack, needle) = (haystack.as_bytes(), needle.as_bytes());
+  if needle.len() > haystack.len() {
+    return None;
+  }
+  let mut end = haystack.len();
+  loop {
+    let pos = memrchr(needle[needle.len() - 1], &haystack[..end])?;
+    if pos + 1 < needle.len() {
+      return None;
+    }
+    let start = pos + 1 - needle.len();
+    if &haystack[start..=pos] == needle {
+      return Some(start);
+    }
+    end = pos;
+  }
+}
+
+/// Returns `true` if `needle` occurs anywhere inside `haystack`
+#[inline]
+pub(crate) fn contains(haystack: &str, needle: &str) -> bool {
+  find(haystack, needle).is_some()
+}
+
+/// Replaces the bytes in `range` with `replacement`, shifting the tail to open or close the gap
+///
+/// # Errors
+///
+/// Returns [`Error::Utf8`] if either endpoint isn't a char boundary, or [`Error::OutOfBounds`]
+/// if the range is inverted, out of bounds, or the resulting string wouldn't fit in `N`
+#[inline]
+pub(crate) fn replace_range<const N: usize>(
+  s: &mut StaticString<N>,
+  range: core::ops::Range<usize>,
+  replacement: &str,
+) -> Result<(), Error>
+{
+  let (start, end) = (range.start, range.end);
+  is_inside_boundary(start, end)?;
+  is_inside_boundary(end, s.len())?;
+  is_char_boundary(s, start)?;
+  is_char_boundary(s, end)?;
+
+  let (old_len, new_len) = (end - start, replacement.len());
+  let total_len = s.len() - old_len + new_len;
+  is_inside_boundary(total_len, s.capacity())?;
+
+  if new_len > old_len {
+    // Safety: `end <= start + new_len` since `new_len > old_len`, and `total_len <= s.capacity()`
+    unsafe { shift_right_unchecked(s, end, start + new_len) };
+  } else if new_len < old_len {
+    // `shift_left_unchecked` copies `s.len() - to` bytes, which over-reads past the string's
+    // logical end for a shrink this large; compute the true tail length ourselves instead
+    let tail_len = s.len() - end;
+    // Safety: `start + new_len + tail_len == total_len <= s.capacity()`, checked above
+    unsafe { shift_unchecked(s.as_mut_bytes(), end, start + new_len, tail_len) };
+  }
+
+  // Safety: `start + new_len <= total_len <= s.capacity()`, checked above
+  unsafe {
+    s
+      .as_mut_bytes()
+      .get_unchecked_mut(start..start + new_len)
+      .copy_from_slice(replacement.as_bytes());
+  }
+  s.size = total_len;
+  Ok(())
+}
+
 impl IntoLossy<u8> for usize {
   #[allow(clippy::cast_possible_truncation)]
   #[inline]
@@ -210,6 +557,145 @@ mod tests {
     assert_eq!(ls.as_str(), "abcdefg");
   }
 
+  #[test]
+  fn from_utf8_lossy() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<20>::from_utf8_lossy(b"hello \xff\xfe world");
+    assert_eq!(s.as_str(), "hello \u{FFFD}\u{FFFD} world");
+  }
+
+  #[test]
+  fn from_utf8_lossy_valid() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<20>::from_utf8_lossy("abc🤔def".as_bytes());
+    assert_eq!(s.as_str(), "abc🤔def");
+  }
+
+  #[test]
+  fn from_utf8_lossy_truncates_at_char_boundary() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<5>::from_utf8_lossy("abc🤔".as_bytes());
+    assert_eq!(s.as_str(), "abc");
+  }
+
+  #[test]
+  fn from_bytes_lossy() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<20>::from_bytes_lossy(b"hi \xffthere");
+    assert_eq!(s.as_str(), "hi \u{FFFD}there");
+  }
+
+  #[test]
+  fn from_utf16() {
+    let _ = env_logger::try_init();
+    // "a🤔" as UTF-16 code units (🤔 is a surrogate pair)
+    let s = StaticString::<20>::from_utf16(&[0x0061, 0xD83E, 0xDD14]).unwrap();
+    assert_eq!(s.as_str(), "a🤔");
+  }
+
+  #[test]
+  fn from_utf16_unpaired_surrogate() {
+    let _ = env_logger::try_init();
+    assert!(StaticString::<20>::from_utf16(&[0xD800]).is_err());
+    assert!(StaticString::<20>::from_utf16(&[0xDC00]).is_err());
+  }
+
+  #[test]
+  fn from_utf16_out_of_bounds() {
+    let _ = env_logger::try_init();
+    assert!(StaticString::<1>::from_utf16(&[0x0061, 0x0062]).is_err());
+  }
+
+  #[test]
+  fn from_utf16_lossy() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<20>::from_utf16_lossy(&[0x0061, 0xD800, 0x0062]);
+    assert_eq!(s.as_str(), "a\u{FFFD}b");
+  }
+
+  #[test]
+  fn from_utf16_lossy_truncates() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<1>::from_utf16_lossy(&[0x0061, 0x0062]);
+    assert_eq!(s.as_str(), "a");
+  }
+
+  #[test]
+  fn memchr() {
+    let _ = env_logger::try_init();
+    assert_eq!(super::memchr(b'c', b"abcdefghijklmnop"), Some(2));
+    assert_eq!(super::memchr(b'z', b"abcdefghijklmnop"), None);
+    assert_eq!(super::memchr(b'p', b"abcdefghijklmnop"), Some(15));
+    assert_eq!(super::memchr(b'a', b""), None);
+  }
+
+  #[test]
+  fn memrchr() {
+    let _ = env_logger::try_init();
+    assert_eq!(super::memrchr(b'a', b"abcabcabcabcabcabc"), Some(15));
+    assert_eq!(super::memrchr(b'z', b"abcabcabcabcabcabc"), None);
+  }
+
+  #[test]
+  fn find() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<20>::try_from_str("hello world").unwrap();
+    assert_eq!(s.find("world"), Some(6));
+    assert_eq!(s.find("xyz"), None);
+    assert_eq!(s.find(""), Some(0));
+  }
+
+  #[test]
+  fn rfind() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<20>::try_from_str("abcabcabc").unwrap();
+    assert_eq!(s.rfind("abc"), Some(6));
+    assert_eq!(s.rfind("xyz"), None);
+  }
+
+  #[test]
+  fn contains() {
+    let _ = env_logger::try_init();
+    let s = StaticString::<20>::try_from_str("hello world").unwrap();
+    assert!(s.contains("lo wo"));
+    assert!(!s.contains("xyz"));
+  }
+
+  #[test]
+  fn replace_range_grow() {
+    let _ = env_logger::try_init();
+    let mut s = StaticString::<24>::try_from_str("hello world").unwrap();
+    s.replace_range(6..11, "there, friend").unwrap();
+    assert_eq!(s.as_str(), "hello there, friend");
+  }
+
+  #[test]
+  fn replace_range_shrink() {
+    let _ = env_logger::try_init();
+    let mut s = StaticString::<24>::try_from_str("hello world").unwrap();
+    s.replace_range(0..5, "hi").unwrap();
+    assert_eq!(s.as_str(), "hi world");
+  }
+
+  #[test]
+  fn replace_range_out_of_bounds() {
+    let _ = env_logger::try_init();
+    let mut s = StaticString::<8>::try_from_str("hello").unwrap();
+    assert!(s.replace_range(0..5, "this is too long").is_err());
+  }
+
+  #[test]
+  fn replace_range_shrink_full_buffer() {
+    let _ = env_logger::try_init();
+    let mut s = StaticString::<10>::try_from_str("0123456789").unwrap();
+    s.replace_range(0..10, "").unwrap();
+    assert_eq!(s.as_str(), "");
+
+    let mut s = StaticString::<10>::try_from_str("0123456789").unwrap();
+    s.replace_range(2..10, "x").unwrap();
+    assert_eq!(s.as_str(), "01x");
+  }
+
   #[test]
   fn encode_char_utf8() {
     let _ = env_logger::try_init();